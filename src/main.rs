@@ -16,9 +16,60 @@ use std::time::Instant;
 const NUM_FRI_QUERIES: usize = 30;
 const LDE_BLOWUP_FACTOR: usize = 30;
 
+// Width of the wide Fibonacci trace i.e. how many consecutive fibonacci
+// numbers are packed into each row. Widening the trace shrinks the number of
+// rows (and therefore the LDE/FFT domain, the dominant cost of proving) by
+// this factor at the cost of `W` columns instead of `1`.
+const W: usize = 16;
+
+// 2-adicity of the Goldilocks field `Fp = 2^64 - 2^32 + 1` i.e. the largest
+// `k` such that `2^k` divides `p - 1`. The LDE domain (trace length times
+// blowup factor) must fit inside a multiplicative subgroup of this order.
+const FP_TWO_ADICITY: u32 = 32;
+
+/// Errors that can be detected before a proof is generated, as opposed to
+/// verification failures which are reported by [`ministark::Proof::verify`].
+#[derive(Debug)]
+enum Error {
+    /// The requested trace length and LDE blowup factor need a domain bigger
+    /// than the field's two-adic subgroup can provide.
+    PolynomialDegreeTooLarge { exp: u32, two_adicity: u32 },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::PolynomialDegreeTooLarge { exp, two_adicity } => write!(
+                f,
+                "LDE domain of size 2^{exp} exceeds the field's two-adic subgroup of size 2^{two_adicity}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Checks that `trace_len * lde_blowup_factor` fits inside `Fp`'s two-adic
+/// subgroup, mirroring the check bellman does in `EvaluationDomain::from_coeffs`.
+/// Calling this from `main` before the trace is built lets us print a
+/// helpful message instead of panicking deep inside the FFT.
+fn check_lde_domain_size(trace_len: usize, lde_blowup_factor: usize) -> Result<(), Error> {
+    let exp = trace_len.trailing_zeros() + lde_blowup_factor.trailing_zeros();
+    if exp > FP_TWO_ADICITY {
+        return Err(Error::PolynomialDegreeTooLarge {
+            exp,
+            two_adicity: FP_TWO_ADICITY,
+        });
+    }
+    Ok(())
+}
+
 fn main() {
     // project goal - convince a verifier we know the 65536th (2^16) fibonacci number
-    let n = 2usize.pow(16);
+    let params = FibParams {
+        n: 2usize.pow(16),
+        w: W,
+    };
 
     // proof options for 128 bit security
     let num_fri_queries = 30;
@@ -34,69 +85,195 @@ fn main() {
         fri_max_remainder_size,
     );
 
-    // 1. generate a nx1 matrix full of fibbonacci numbers (prover only)
-    let fib_matrix = build_fib_matrix(n);
+    if let Err(err) = check_lde_domain_size(params.n / params.w, lde_blowup_factor) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
 
-    // 2. generate STARK proof
+    // 1. generate STARK proof. `FibTraceGenerator` only introduces the
+    //    `TraceGenerator` trait shape for building the fibonacci matrix
+    //    (see its doc comment below) — `generate_proof` still takes a
+    //    pre-built `FibTrace`, so the framework doesn't yet own allocation,
+    //    and nothing here is reused or streamed across calls. Making
+    //    `generate_proof` itself accept a generator is a separate, larger
+    //    change to `ministark::Prover`'s signature, deferred for now.
     let prover = FibProver::new(proof_options);
-    let trace = FibTrace::new(fib_matrix);
+    let generator = FibTraceGenerator;
+    let trace = FibTrace::new(generator.base_trace(&params), params.w);
     println!("Generating proof");
     let now = Instant::now();
     let proof = pollster::block_on(prover.generate_proof(trace)).unwrap();
     println!("Generated proof in {:?}", now.elapsed());
     println!("Proof security {}-bit", proof.conjectured_security_level());
 
-    // 3. verify STARK proof
+    // 2. verify STARK proof
     println!("Verifying proof");
     let now = Instant::now();
     proof.verify().unwrap();
     println!("Proof verified in {:?}", now.elapsed());
 }
 
+/// `(looked_up_columns, table_columns)` pairs this instance needs checked as
+/// equal multisets, returned by [`FibAir::lookups`].
+/// [`FibTrace::build_aux_columns`] does not read it and always returns an
+/// empty matrix, and no constraints are built from it. The wide fibonacci layout
+/// doesn't cross-reference rows through a table, so this is empty; a variant
+/// that range-checks each cell against a lookup table would declare a pair
+/// here per checked column.
+fn fib_lookups(_params: &FibParams) -> Vec<(Vec<usize>, Vec<usize>)> {
+    Vec::new()
+}
+
+// Compiling a declared `(looked_up_columns, table_columns)` lookup into
+// actual running-product constraints needs two things this crate can't
+// honestly provide yet:
+//   1. Fiat-Shamir-derived `alpha`/`z` challenges pulled from `Hints<Fp>`'s
+//      committed aux-trace transcript — without real randomness the
+//      product-equality check has no soundness, since a prover could satisfy
+//      it without the looked-up/table columns actually matching as
+//      multisets. `ministark::hints::Hints`'s accessor API for this isn't
+//      pinned down in this tree.
+//   2. A way to address aux-phase columns in `AlgebraicExpression` — the
+//      only column-referencing variant, `Trace`, addresses the base trace
+//      (`FibTrace::NUM_BASE_COLUMNS` wide); there's no aux-column variant to
+//      build a sound accumulator constraint against.
+// `fib_lookups` above still declares (today, an empty) set of pairs so
+// `Air::lookups` has a real implementation to call, but compiling that
+// declaration into constraints and a matching witness is deferred until
+// both of the above are available upstream, the same way chunk0-5 deferred
+// recursive verification rather than landing a stub that's silently
+// unsound the moment a caller declares a real lookup.
+
 struct FibTrace {
     execution_trace: Matrix<Fp>,
+    w: usize,
 }
 
 impl FibTrace {
-    fn new(execution_trace: Matrix<Fp>) -> Self {
-        FibTrace { execution_trace }
+    fn new(execution_trace: Matrix<Fp>, w: usize) -> Self {
+        FibTrace { execution_trace, w }
     }
 
     fn last_fib_number(&self) -> Fp {
-        let n = self.execution_trace.num_rows();
-        self.execution_trace.get_row(n - 1).unwrap()[0]
+        let last_row = self.execution_trace.num_rows() - 1;
+        self.execution_trace.get_row(last_row).unwrap()[self.w - 1]
+    }
+}
+
+/// Produces the base trace for a `Trace` impl; it has no method for an
+/// interaction-phase trace. Pulling matrix construction out of `main`'s call site and
+/// behind this trait is groundwork for `Prover` itself owning allocation
+/// (including the GPU's `PageAlignedAllocator` requirement) and reusing one
+/// generator across many indices or batched proving runs — `generate_proof`
+/// doesn't take a generator yet (see the note in `main`), so today this is
+/// just the trait shape, called once from `main` the same as before.
+trait TraceGenerator {
+    type Fp;
+    type Inputs;
+
+    fn base_trace(&self, inputs: &Self::Inputs) -> Matrix<Self::Fp>;
+}
+
+struct FibTraceGenerator;
+
+impl TraceGenerator for FibTraceGenerator {
+    type Fp = Fp;
+    type Inputs = FibParams;
+
+    fn base_trace(&self, inputs: &Self::Inputs) -> Matrix<Self::Fp> {
+        build_fib_matrix(inputs.n, inputs.w)
     }
 }
 
 impl Trace for FibTrace {
-    const NUM_BASE_COLUMNS: usize = 1;
+    const NUM_BASE_COLUMNS: usize = W;
     type Fp = Fp;
     type Fq = Fp;
 
     fn base_columns(&self) -> &Matrix<Self::Fp> {
         &self.execution_trace
     }
+
+    // `fib_lookups` never declares a lookup, and compiling a declared one
+    // into a running-product witness is deferred (see the note above
+    // `FibTrace`), so there are no aux columns to build yet.
+    fn build_aux_columns(&self, _hints: &Hints<Self::Fq>) -> Matrix<Self::Fq> {
+        Matrix::new(Vec::new())
+    }
+}
+
+/// Structural configuration for [`FibAir`] i.e. everything that shapes the
+/// constraint system rather than the witness values it's checking. Borrowed
+/// from halo2's configurable-circuit pattern so a single `FibAir` impl can be
+/// reused for any fibonacci index without recompiling. `w` is still pinned to
+/// the compile-time column count `W` (`FibTrace::NUM_BASE_COLUMNS` can't vary
+/// at runtime); it's carried here so `FibAir::new` can catch a mismatch
+/// instead of silently reading the wrong cell as the claimed fibonacci number.
+#[derive(Clone, Copy)]
+struct FibParams {
+    /// How many fibonacci numbers are being proved. Must be a power of two.
+    n: usize,
+    /// How many consecutive fibonacci numbers are packed into each trace row.
+    w: usize,
+}
+
+impl Default for FibParams {
+    fn default() -> Self {
+        FibParams {
+            n: 2usize.pow(16),
+            w: W,
+        }
+    }
 }
 
 struct FibAir {
     info: ministark::TraceInfo,
     input: Fp,
     options: ProofOptions,
+    params: FibParams,
 }
 
 impl Air for FibAir {
     type Fp = Fp;
     type Fq = Fp;
     type PublicInputs = Fp;
-
-    fn new(info: TraceInfo, input: Self::PublicInputs, options: ProofOptions) -> Self {
+    type Params = FibParams;
+
+    fn new(
+        info: TraceInfo,
+        input: Self::PublicInputs,
+        options: ProofOptions,
+        params: Self::Params,
+    ) -> Self {
+        assert_eq!(
+            info.trace_len(),
+            params.n / params.w,
+            "trace length does not match the configured index/width"
+        );
+        assert_eq!(
+            params.w, W,
+            "params.w must match the compiled column count W until NUM_BASE_COLUMNS can vary at runtime"
+        );
         FibAir {
             info,
             input,
             options,
+            params,
         }
     }
 
+    fn params(&self) -> Self::Params {
+        self.params
+    }
+
+    // Declares the `(looked_up_columns, table_columns)` pairs this instance
+    // wants checked as equal multisets. Compiling a declared pair into
+    // constraints is deferred (see the note above `FibTrace`), so this is
+    // only exercised once that lands; `fib_lookups` returns nothing today.
+    fn lookups(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        fib_lookups(&self.params)
+    }
+
     fn pub_inputs(&self) -> &Self::PublicInputs {
         &self.input
     }
@@ -114,6 +291,7 @@ impl Air for FibAir {
 
         let one = Constant(FieldConstant::Fp(Fp::one()));
         let claimed_nth_fib_num: AlgebraicExpression<Fp> = Constant(FieldConstant::Fp(self.input));
+        let w = self.params().w;
 
         // Domain we use to interpolate execution trace
         let trace_xs = self.trace_domain();
@@ -122,29 +300,32 @@ impl Air for FibAir {
         // NOTE: x^n - 1 = (x - ⍵_n^0)(x - ⍵_n^1)(x - ⍵_n^2)...(x - ⍵_n^(n-1))
         let vanish_all_rows: AlgebraicExpression<Fp> = X.pow(n) - &one;
         let vanish_first_row: AlgebraicExpression<Fp> = X - FieldConstant::Fp(trace_xs.element(0));
-        let vanish_second_row: AlgebraicExpression<Fp> = X - FieldConstant::Fp(trace_xs.element(1));
         let vanish_last_row: AlgebraicExpression<Fp> =
             X - FieldConstant::Fp(trace_xs.element(n - 1));
 
-        let column = 0;
-        let row_offset = 0;
-        let curr_row: AlgebraicExpression<Fp> = Trace(column, row_offset);
-
-        let column = 0;
-        let row_offset = -1;
-        let before_row: AlgebraicExpression<Fp> = Trace(column, row_offset);
-
-        let column = 0;
-        let row_offset = -2;
-        let two_before_row: AlgebraicExpression<Fp> = Trace(column, row_offset);
+        // cell `c` of the current/previous row, as laid out by `build_fib_matrix`
+        let cell = |column: usize, row_offset: isize| -> AlgebraicExpression<Fp> {
+            Trace(column, row_offset)
+        };
+
+        let mut constraints = vec![
+            // 1. the first row's leftmost two columns seed the sequence with 1, 1
+            (cell(0, 0) - &one) / &vanish_first_row,
+            (cell(1, 0) - &one) / &vanish_first_row,
+            // 2. every row after the first continues the sequence from the last two
+            //    cells of the row before it (guarded so it doesn't apply to row 0)
+            (cell(0, 0) - cell(w - 1, -1) - cell(w - 2, -1)) / (&vanish_all_rows / &vanish_first_row),
+            (cell(1, 0) - cell(0, 0) - cell(w - 1, -1)) / (&vanish_all_rows / &vanish_first_row),
+            // 4. the trace's final cell must equal the prover's claimed nth fibonacci number
+            (cell(w - 1, 0) - &claimed_nth_fib_num) / &vanish_last_row,
+        ];
+
+        // 3. within a row, every remaining cell is the sum of its two predecessors
+        for column in 2..w {
+            constraints.push((cell(column, 0) - cell(column - 1, 0) - cell(column - 2, 0)) / &vanish_all_rows);
+        }
 
-        vec![
-            // 1. first row must equal 1
-            // 2. second row must equal 1
-            // 3. remainig rows must equal the sum of their two preceding rows
-            // 4. last row must equal the the prover's claimed `n`th fibonacci number
-            todo!(),
-        ]
+        constraints
     }
 }
 
@@ -171,36 +352,48 @@ impl Prover for FibProver {
     }
 }
 
-/// Fills a matrix with the fibonacci numbers
-//  P(x)
-// ┌───────┐
-// │ 1     │ <- P(⍵_n^0) = 1
-// ├───────┤
-// │ 1     │ <- P(⍵_n^1) = 1
-// ├───────┤
-// │ 2     │ <- P(⍵_n^2) = 2
-// ├───────┤
-// │ 3     │ <- ...
-// ├───────┤
-// │ 5     │
-// ├───────┤
-// │  ...  │
-// ├───────┤
-// │ fib_n │
-// └───────┘
-fn build_fib_matrix(n: usize) -> Matrix<Fp> {
+// Recursive proof composition (re-verifying a FRI-STARK for this Fibonacci
+// Air as constraints in an outer STARK, mirroring how starky re-verifies a
+// FRI-STARK inside a plonky2 SNARK) is deliberately not started here. Doing
+// it honestly needs `ministark::Proof` to expose its query openings, Merkle
+// authentication paths and FRI layer commitments, none of which are public
+// in this version of the crate, and the Merkle-path/DEEP/FRI-folding/
+// grinding-nonce constraint shapes are substantial enough to warrant their
+// own change once that's available. Tracked as a follow-up rather than
+// landed as an unusable stub.
+
+/// Fills a `w`-wide matrix with the fibonacci numbers, packing `w` consecutive
+/// numbers into each row instead of one per row. This shrinks the trace
+/// height (and therefore the LDE/FFT domain) by a factor of `w`.
+//     Trace(0,r) Trace(1,r) ... Trace(w-1,r)
+// ┌───────────┬───────────┬───┬─────────────┐
+// │     1     │     1     │ ..│   fib_(w-1) │ <- row 0
+// ├───────────┼───────────┼───┼─────────────┤
+// │  fib_w    │ fib_(w+1) │ ..│ fib_(2w-1)  │ <- row 1
+// ├───────────┼───────────┼───┼─────────────┤
+// │    ...    │    ...    │ ..│     ...     │
+// └───────────┴───────────┴───┴─────────────┘
+fn build_fib_matrix(n: usize, w: usize) -> Matrix<Fp> {
     assert!(n.is_power_of_two());
+    assert!(w.is_power_of_two());
+    assert!(w >= 2, "trace width must fit the two leftmost seed columns");
+    assert!(n >= w, "trace width must not exceed the number of fibonacci numbers requested");
+
+    // generate the flat sequence first; column `c` of row `r` holds number `r * w + c`
+    let mut flat = Vec::with_capacity(n);
+    flat.push(Fp::one());
+    flat.push(Fp::one());
+    for i in 2..n {
+        flat.push(flat[i - 1] + flat[i - 2]);
+    }
 
     // The GPU only accepts page aligned memory
-    let mut column = Vec::new_in(PageAlignedAllocator);
-
-    // initial fibonacci numbers
-    column.push(Fp::one());
-    column.push(Fp::one());
-
-    for i in 2..n {
-        column.push(column[i - 1] + column[i - 2]);
+    let mut columns = (0..w)
+        .map(|_| Vec::with_capacity_in(n / w, PageAlignedAllocator))
+        .collect::<Vec<_>>();
+    for (i, fib_num) in flat.into_iter().enumerate() {
+        columns[i % w].push(fib_num);
     }
 
-    Matrix::new(vec![column])
+    Matrix::new(columns)
 }